@@ -0,0 +1,371 @@
+use crate::err::Error;
+use crate::sql::array::Array;
+use crate::sql::object::Object;
+use crate::sql::value::serde::de::from_value;
+use crate::sql::value::serde::ser::value::to_value;
+use crate::sql::Datetime;
+use crate::sql::Number;
+use crate::sql::Strand;
+use crate::sql::Uuid;
+use crate::sql::Value;
+use apache_avro::schema::RecordField;
+use apache_avro::types::Value as Avro;
+use apache_avro::Decimal;
+use apache_avro::Schema;
+use bigdecimal::BigDecimal;
+use bigdecimal::ToPrimitive;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use storekey::decode::Error as DecodeError;
+use storekey::encode::Error as EncodeError;
+
+fn encode_err(path: &str, err: impl ToString) -> Error {
+	Error::Encode(EncodeError::Message(format!("{path}: {}", err.to_string())))
+}
+
+fn decode_err(path: &str, err: impl ToString) -> Error {
+	Error::Decode(DecodeError::Message(format!("{path}: {}", err.to_string())))
+}
+
+/// Serialize a `T` into Avro binary, walking `schema` and the resulting [`Value`] in lock-step so
+/// the wire format matches exactly what the schema declares rather than being self-describing.
+pub(crate) fn to_avro<T>(value: &T, schema: &Schema) -> Result<Vec<u8>, Error>
+where
+	T: Serialize,
+{
+	let avro = value_to_avro(&to_value(value)?, schema, "$")?;
+	apache_avro::to_avro_datum(schema, avro).map_err(|e| encode_err("$", e))
+}
+
+/// Resolve Avro binary produced against `schema` back into a `T`.
+pub(crate) fn from_avro<T>(mut bytes: &[u8], schema: &Schema) -> Result<T, Error>
+where
+	T: DeserializeOwned,
+{
+	let avro = apache_avro::from_avro_datum(schema, &mut bytes, None)
+		.map_err(|e| decode_err("$", e))?;
+	from_value(avro_to_value(avro, schema, "$")?)
+}
+
+fn number_i64(path: &str, value: &Value) -> Result<i64, Error> {
+	match value {
+		Value::Number(Number::Int(v)) => Ok(*v),
+		Value::Number(Number::Float(v)) => Ok(*v as i64),
+		Value::Number(Number::Decimal(v)) => {
+			v.to_i64().ok_or_else(|| encode_err(path, format!("`{v}` does not fit in an i64")))
+		}
+		value => Err(encode_err(path, format!("expected a number, found `{value}`"))),
+	}
+}
+
+fn number_f64(path: &str, value: &Value) -> Result<f64, Error> {
+	match value {
+		Value::Number(Number::Int(v)) => Ok(*v as f64),
+		Value::Number(Number::Float(v)) => Ok(*v),
+		Value::Number(Number::Decimal(v)) => {
+			v.to_f64().ok_or_else(|| encode_err(path, format!("`{v}` does not fit in an f64")))
+		}
+		value => Err(encode_err(path, format!("expected a number, found `{value}`"))),
+	}
+}
+
+fn bytes_of(path: &str, value: &Value) -> Result<Vec<u8>, Error> {
+	match value {
+		Value::Array(Array(elements)) => elements
+			.iter()
+			.map(|element| match element {
+				Value::Number(n) => Ok(number_i64(path, &Value::Number(n.clone()))? as u8),
+				element => Err(encode_err(path, format!("expected a byte, found `{element}`"))),
+			})
+			.collect(),
+		value => Err(encode_err(path, format!("expected bytes, found `{value}`"))),
+	}
+}
+
+fn decimal_to_avro(path: &str, decimal: &BigDecimal, scale: usize) -> Result<Avro, Error> {
+	let (unscaled, exponent) = decimal.as_bigint_and_exponent();
+	let scale = i64::try_from(scale).map_err(|e| encode_err(path, e))?;
+	let unscaled = if exponent == scale {
+		unscaled
+	} else if exponent < scale {
+		unscaled * num_bigint::BigInt::from(10).pow((scale - exponent) as u32)
+	} else {
+		unscaled / num_bigint::BigInt::from(10).pow((exponent - scale) as u32)
+	};
+	Ok(Avro::Decimal(Decimal::from(unscaled.to_signed_bytes_be())))
+}
+
+fn avro_to_decimal(path: &str, decimal: Decimal, scale: usize) -> Result<BigDecimal, Error> {
+	let bytes: Vec<u8> = decimal.try_into().map_err(|e| decode_err(path, format!("{e:?}")))?;
+	let unscaled = num_bigint::BigInt::from_signed_bytes_be(&bytes);
+	Ok(BigDecimal::new(unscaled, scale as i64))
+}
+
+fn value_to_avro(value: &Value, schema: &Schema, path: &str) -> Result<Avro, Error> {
+	Ok(match schema {
+		Schema::Null => match value {
+			Value::None | Value::Null => Avro::Null,
+			value => return Err(encode_err(path, format!("expected null, found `{value}`"))),
+		},
+		Schema::Boolean => match value {
+			Value::False => Avro::Boolean(false),
+			Value::True => Avro::Boolean(true),
+			value => return Err(encode_err(path, format!("expected a bool, found `{value}`"))),
+		},
+		Schema::Int => Avro::Int(number_i64(path, value)? as i32),
+		Schema::Long => Avro::Long(number_i64(path, value)?),
+		Schema::Float => Avro::Float(number_f64(path, value)? as f32),
+		Schema::Double => Avro::Double(number_f64(path, value)?),
+		Schema::Bytes => Avro::Bytes(bytes_of(path, value)?),
+		Schema::Fixed(fixed) => {
+			let bytes = bytes_of(path, value)?;
+			if bytes.len() != fixed.size {
+				return Err(encode_err(
+					path,
+					format!("expected {} fixed bytes, found {}", fixed.size, bytes.len()),
+				));
+			}
+			Avro::Fixed(fixed.size, bytes)
+		}
+		Schema::String => match value {
+			Value::Strand(Strand(v)) => Avro::String(v.clone()),
+			value => return Err(encode_err(path, format!("expected a string, found `{value}`"))),
+		},
+		Schema::Enum(schema) => match value {
+			Value::Strand(Strand(v)) => match schema.symbols.iter().position(|s| s == v) {
+				Some(index) => Avro::Enum(index as u32, v.clone()),
+				None => return Err(encode_err(path, format!("`{v}` is not a symbol of this enum"))),
+			},
+			value => return Err(encode_err(path, format!("expected an enum symbol, found `{value}`"))),
+		},
+		Schema::Uuid => match value {
+			Value::Uuid(Uuid(v)) => Avro::Uuid(*v),
+			value => return Err(encode_err(path, format!("expected a uuid, found `{value}`"))),
+		},
+		Schema::TimestampMillis => match value {
+			Value::Datetime(Datetime(v)) => Avro::TimestampMillis(v.timestamp_millis()),
+			value => return Err(encode_err(path, format!("expected a datetime, found `{value}`"))),
+		},
+		Schema::TimestampMicros => match value {
+			Value::Datetime(Datetime(v)) => Avro::TimestampMicros(v.timestamp_micros()),
+			value => return Err(encode_err(path, format!("expected a datetime, found `{value}`"))),
+		},
+		Schema::Decimal(schema) => match value {
+			Value::Number(Number::Decimal(v)) => decimal_to_avro(path, v, schema.scale)?,
+			value => return Err(encode_err(path, format!("expected a decimal, found `{value}`"))),
+		},
+		Schema::Union(union) => {
+			let mut last_err = None;
+			let mut result = None;
+			for (index, branch) in union.variants().iter().enumerate() {
+				match value_to_avro(value, branch, path) {
+					Ok(avro) => {
+						result = Some(Avro::Union(index as u32, Box::new(avro)));
+						break;
+					}
+					Err(err) => last_err = Some(err),
+				}
+			}
+			result.ok_or_else(|| {
+				last_err.unwrap_or_else(|| encode_err(path, "no branch of this union matched"))
+			})?
+		}
+		Schema::Array(schema) => match value {
+			Value::Array(Array(elements)) => Avro::Array(
+				elements
+					.iter()
+					.enumerate()
+					.map(|(i, element)| value_to_avro(element, &schema.items, &format!("{path}[{i}]")))
+					.collect::<Result<_, _>>()?,
+			),
+			value => return Err(encode_err(path, format!("expected an array, found `{value}`"))),
+		},
+		Schema::Map(schema) => match value {
+			Value::Object(Object(map)) => Avro::Map(
+				map.iter()
+					.map(|(k, v)| Ok((k.clone(), value_to_avro(v, &schema.types, &format!("{path}.{k}"))?)))
+					.collect::<Result<HashMap<_, _>, Error>>()?,
+			),
+			value => return Err(encode_err(path, format!("expected a map, found `{value}`"))),
+		},
+		Schema::Record(schema) => match value {
+			Value::Object(Object(map)) => {
+				let mut fields = Vec::with_capacity(schema.fields.len());
+				for field in &schema.fields {
+					let field_path = format!("{path}.{}", field.name);
+					let avro = match map.get(&field.name) {
+						Some(value) => value_to_avro(value, &field.schema, &field_path)?,
+						None => default_to_avro(field, &field_path)?,
+					};
+					fields.push((field.name.clone(), avro));
+				}
+				Avro::Record(fields)
+			}
+			value => return Err(encode_err(path, format!("expected an object, found `{value}`"))),
+		},
+		schema => return Err(encode_err(path, format!("unsupported Avro schema `{schema:?}`"))),
+	})
+}
+
+fn default_to_avro(field: &RecordField, path: &str) -> Result<Avro, Error> {
+	match &field.default {
+		Some(default) => {
+			let value: Value = serde_json::from_value(default.clone()).map_err(|e| encode_err(path, e))?;
+			value_to_avro(&value, &field.schema, path)
+		}
+		None => Err(encode_err(path, "missing field and no default is declared")),
+	}
+}
+
+fn avro_to_value(avro: Avro, schema: &Schema, path: &str) -> Result<Value, Error> {
+	Ok(match (avro, schema) {
+		(Avro::Null, _) => Value::None,
+		(Avro::Boolean(false), _) => Value::False,
+		(Avro::Boolean(true), _) => Value::True,
+		(Avro::Int(v), _) => Number::Int(v.into()).into(),
+		(Avro::Long(v), _) => Number::Int(v).into(),
+		(Avro::Float(v), _) => Number::Float(v.into()).into(),
+		(Avro::Double(v), _) => Number::Float(v).into(),
+		(Avro::Bytes(v), _) | (Avro::Fixed(_, v), _) => {
+			Array(v.into_iter().map(|b| Number::Int(b.into()).into()).collect()).into()
+		}
+		(Avro::String(v), _) => Strand(v).into(),
+		(Avro::Enum(_, symbol), _) => Strand(symbol).into(),
+		(Avro::Uuid(v), _) => Uuid(v).into(),
+		(Avro::TimestampMillis(v), _) => {
+			let secs = v.div_euclid(1000);
+			let millis = v.rem_euclid(1000);
+			let datetime = chrono::Utc
+				.timestamp_opt(secs, (millis * 1_000_000) as u32)
+				.single()
+				.ok_or_else(|| decode_err(path, "invalid millisecond timestamp"))?;
+			Datetime(datetime).into()
+		}
+		(Avro::TimestampMicros(v), _) => {
+			let secs = v.div_euclid(1_000_000);
+			let micros = v.rem_euclid(1_000_000);
+			let datetime = chrono::Utc
+				.timestamp_opt(secs, (micros * 1_000) as u32)
+				.single()
+				.ok_or_else(|| decode_err(path, "invalid microsecond timestamp"))?;
+			Datetime(datetime).into()
+		}
+		(Avro::Decimal(v), Schema::Decimal(schema)) => {
+			Number::Decimal(avro_to_decimal(path, v, schema.scale)?).into()
+		}
+		(Avro::Union(index, inner), Schema::Union(union)) => {
+			let branch = union
+				.variants()
+				.get(index as usize)
+				.ok_or_else(|| decode_err(path, "union branch index out of range"))?;
+			avro_to_value(*inner, branch, path)?
+		}
+		(Avro::Array(elements), Schema::Array(schema)) => Array(
+			elements
+				.into_iter()
+				.enumerate()
+				.map(|(i, element)| avro_to_value(element, &schema.items, &format!("{path}[{i}]")))
+				.collect::<Result<_, _>>()?,
+		)
+		.into(),
+		(Avro::Map(entries), Schema::Map(schema)) => {
+			let mut object = Object::default();
+			for (k, v) in entries {
+				let field_path = format!("{path}.{k}");
+				object.insert(k, avro_to_value(v, &schema.types, &field_path)?);
+			}
+			object.into()
+		}
+		(Avro::Record(fields), Schema::Record(schema)) => {
+			let mut object = Object::default();
+			for (name, value) in fields {
+				let field = schema
+					.fields
+					.iter()
+					.find(|f| f.name == name)
+					.ok_or_else(|| decode_err(path, format!("unknown record field `{name}`")))?;
+				let field_path = format!("{path}.{name}");
+				object.insert(name, avro_to_value(value, &field.schema, &field_path)?);
+			}
+			object.into()
+		}
+		(avro, schema) => {
+			return Err(decode_err(path, format!("`{avro:?}` does not match schema `{schema:?}`")))
+		}
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sql::*;
+
+	#[test]
+	fn record_with_default() {
+		let schema = Schema::parse_str(
+			r#"{
+				"type": "record",
+				"name": "Person",
+				"fields": [
+					{"name": "name", "type": "string"},
+					{"name": "age", "type": "long", "default": 0}
+				]
+			}"#,
+		)
+		.unwrap();
+
+		let mut object = Object::default();
+		object.insert("name".to_owned(), Value::from("Ada"));
+
+		let bytes = to_avro(&object, &schema).unwrap();
+		let decoded: Object = from_avro(&bytes, &schema).unwrap();
+
+		let mut expected = Object::default();
+		expected.insert("name".to_owned(), Value::from("Ada"));
+		expected.insert("age".to_owned(), Value::from(0));
+		assert_eq!(decoded, expected);
+	}
+
+	#[test]
+	fn float_and_double_keep_their_fractional_part() {
+		let schema = Schema::parse_str(
+			r#"{
+				"type": "record",
+				"name": "Measurement",
+				"fields": [
+					{"name": "a", "type": "float"},
+					{"name": "b", "type": "double"}
+				]
+			}"#,
+		)
+		.unwrap();
+
+		let mut object = Object::default();
+		object.insert("a".to_owned(), Value::from(Number::Float(3.14)));
+		object.insert("b".to_owned(), Value::from(Number::Decimal("2.5".parse().unwrap())));
+
+		let bytes = to_avro(&object, &schema).unwrap();
+		let decoded: Object = from_avro(&bytes, &schema).unwrap();
+
+		assert_eq!(decoded.get("a"), Some(&Value::from(Number::Float(3.14_f32 as f64))));
+		assert_eq!(decoded.get("b"), Some(&Value::from(Number::Float(2.5))));
+	}
+
+	#[test]
+	fn missing_field_without_default_is_an_error() {
+		let schema = Schema::parse_str(
+			r#"{
+				"type": "record",
+				"name": "Person",
+				"fields": [
+					{"name": "name", "type": "string"}
+				]
+			}"#,
+		)
+		.unwrap();
+
+		let object = Object::default();
+		assert!(to_avro(&object, &schema).is_err());
+	}
+}
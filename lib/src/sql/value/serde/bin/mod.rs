@@ -0,0 +1,352 @@
+use crate::err::Error;
+use crate::sql::array::Array;
+use crate::sql::object::Object;
+use crate::sql::value::serde::de::from_value;
+use crate::sql::value::serde::ser::value::to_value;
+use crate::sql::Duration;
+use crate::sql::Number;
+use crate::sql::Strand;
+use crate::sql::Uuid;
+use crate::sql::Value;
+use chrono::TimeZone;
+use serde::de::DeserializeOwned;
+use serde::ser::Error as _;
+use serde::Serialize;
+use std::io;
+use storekey::decode::Error as DecodeError;
+use storekey::encode::Error as EncodeError;
+
+const TAG_NONE: u8 = 0;
+const TAG_NULL: u8 = 1;
+const TAG_FALSE: u8 = 2;
+const TAG_TRUE: u8 = 3;
+const TAG_INT: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_DECIMAL: u8 = 6;
+const TAG_STRAND: u8 = 7;
+const TAG_DURATION: u8 = 8;
+const TAG_DATETIME: u8 = 9;
+const TAG_UUID: u8 = 10;
+const TAG_ARRAY: u8 = 11;
+const TAG_OBJECT: u8 = 12;
+
+/// Serialize a `T` into the deterministic, big-endian binary wire format.
+///
+/// Two equal values always produce identical bytes: object keys are written in sorted order
+/// regardless of insertion order, which the `map!`-based [`Object`] does not otherwise guarantee.
+/// This is distinct from the storekey key-encoding used for index keys - it exists for
+/// content-addressable hashing and compact on-wire transport of whole values.
+pub(crate) fn to_vec<T>(value: &T) -> Result<Vec<u8>, Error>
+where
+	T: Serialize,
+{
+	let mut out = Vec::new();
+	to_writer(value, &mut out)?;
+	Ok(out)
+}
+
+/// Write a `T` into `writer` using the binary wire format.
+pub(crate) fn to_writer<T, W>(value: &T, writer: &mut W) -> Result<(), Error>
+where
+	T: Serialize,
+	W: io::Write,
+{
+	write_value(&to_value(value)?, writer)
+}
+
+/// Read a `T` back from the binary wire format.
+pub(crate) fn from_slice<T>(bytes: &[u8]) -> Result<T, Error>
+where
+	T: DeserializeOwned,
+{
+	from_reader(&mut io::Cursor::new(bytes))
+}
+
+/// Read a `T` back from the binary wire format via `reader`.
+pub(crate) fn from_reader<T, R>(reader: &mut R) -> Result<T, Error>
+where
+	T: DeserializeOwned,
+	R: io::Read,
+{
+	from_value(read_value(reader)?)
+}
+
+fn encode_err(err: impl ToString) -> Error {
+	Error::Encode(EncodeError::Message(err.to_string()))
+}
+
+fn decode_err(err: impl ToString) -> Error {
+	Error::Decode(DecodeError::Message(err.to_string()))
+}
+
+fn write_varint<W>(writer: &mut W, mut value: u64) -> Result<(), Error>
+where
+	W: io::Write,
+{
+	loop {
+		let byte = (value & 0x7f) as u8;
+		value >>= 7;
+		if value == 0 {
+			writer.write_all(&[byte]).map_err(encode_err)?;
+			return Ok(());
+		}
+		writer.write_all(&[byte | 0x80]).map_err(encode_err)?;
+	}
+}
+
+fn read_varint<R>(reader: &mut R) -> Result<u64, Error>
+where
+	R: io::Read,
+{
+	let mut value = 0u64;
+	let mut shift = 0;
+	loop {
+		let mut byte = [0u8; 1];
+		reader.read_exact(&mut byte).map_err(decode_err)?;
+		value |= u64::from(byte[0] & 0x7f) << shift;
+		if byte[0] & 0x80 == 0 {
+			return Ok(value);
+		}
+		shift += 7;
+	}
+}
+
+fn write_bytes<W>(writer: &mut W, bytes: &[u8]) -> Result<(), Error>
+where
+	W: io::Write,
+{
+	write_varint(writer, bytes.len() as u64)?;
+	writer.write_all(bytes).map_err(encode_err)
+}
+
+fn read_bytes<R>(reader: &mut R) -> Result<Vec<u8>, Error>
+where
+	R: io::Read,
+{
+	let len = read_varint(reader)?;
+	let mut bytes = vec![0u8; len as usize];
+	reader.read_exact(&mut bytes).map_err(decode_err)?;
+	Ok(bytes)
+}
+
+fn write_value<W>(value: &Value, writer: &mut W) -> Result<(), Error>
+where
+	W: io::Write,
+{
+	match value {
+		Value::None => writer.write_all(&[TAG_NONE]).map_err(encode_err),
+		Value::Null => writer.write_all(&[TAG_NULL]).map_err(encode_err),
+		Value::False => writer.write_all(&[TAG_FALSE]).map_err(encode_err),
+		Value::True => writer.write_all(&[TAG_TRUE]).map_err(encode_err),
+		Value::Number(Number::Int(v)) => {
+			writer.write_all(&[TAG_INT]).map_err(encode_err)?;
+			writer.write_all(&v.to_be_bytes()).map_err(encode_err)
+		}
+		Value::Number(Number::Float(v)) => {
+			writer.write_all(&[TAG_FLOAT]).map_err(encode_err)?;
+			writer.write_all(&v.to_be_bytes()).map_err(encode_err)
+		}
+		Value::Number(Number::Decimal(v)) => {
+			writer.write_all(&[TAG_DECIMAL]).map_err(encode_err)?;
+			// Normalize first: numerically-equal decimals with different scales (`1.5` vs
+			// `1.50`) must produce identical bytes to uphold this module's determinism guarantee.
+			write_bytes(writer, v.normalized().to_string().as_bytes())
+		}
+		Value::Strand(Strand(v)) => {
+			writer.write_all(&[TAG_STRAND]).map_err(encode_err)?;
+			write_bytes(writer, v.as_bytes())
+		}
+		Value::Duration(Duration(v)) => {
+			writer.write_all(&[TAG_DURATION]).map_err(encode_err)?;
+			writer.write_all(&v.as_secs().to_be_bytes()).map_err(encode_err)?;
+			writer.write_all(&v.subsec_nanos().to_be_bytes()).map_err(encode_err)
+		}
+		Value::Datetime(v) => {
+			writer.write_all(&[TAG_DATETIME]).map_err(encode_err)?;
+			writer.write_all(&v.0.timestamp().to_be_bytes()).map_err(encode_err)?;
+			writer.write_all(&v.0.timestamp_subsec_nanos().to_be_bytes()).map_err(encode_err)
+		}
+		Value::Uuid(Uuid(v)) => {
+			writer.write_all(&[TAG_UUID]).map_err(encode_err)?;
+			writer.write_all(v.as_bytes()).map_err(encode_err)
+		}
+		Value::Array(Array(v)) => {
+			writer.write_all(&[TAG_ARRAY]).map_err(encode_err)?;
+			write_varint(writer, v.len() as u64)?;
+			for element in v {
+				write_value(element, writer)?;
+			}
+			Ok(())
+		}
+		Value::Object(Object(v)) => {
+			writer.write_all(&[TAG_OBJECT]).map_err(encode_err)?;
+			write_varint(writer, v.len() as u64)?;
+			let mut keys: Vec<&String> = v.keys().collect();
+			keys.sort();
+			for key in keys {
+				write_bytes(writer, key.as_bytes())?;
+				write_value(&v[key], writer)?;
+			}
+			Ok(())
+		}
+		value => Err(encode_err(format!("`{value}` has no binary wire representation"))),
+	}
+}
+
+fn read_value<R>(reader: &mut R) -> Result<Value, Error>
+where
+	R: io::Read,
+{
+	let mut tag = [0u8; 1];
+	reader.read_exact(&mut tag).map_err(decode_err)?;
+	match tag[0] {
+		TAG_NONE => Ok(Value::None),
+		TAG_NULL => Ok(Value::Null),
+		TAG_FALSE => Ok(Value::False),
+		TAG_TRUE => Ok(Value::True),
+		TAG_INT => {
+			let mut bytes = [0u8; 8];
+			reader.read_exact(&mut bytes).map_err(decode_err)?;
+			Ok(Number::Int(i64::from_be_bytes(bytes)).into())
+		}
+		TAG_FLOAT => {
+			let mut bytes = [0u8; 8];
+			reader.read_exact(&mut bytes).map_err(decode_err)?;
+			Ok(Number::Float(f64::from_be_bytes(bytes)).into())
+		}
+		TAG_DECIMAL => {
+			let bytes = read_bytes(reader)?;
+			let text = String::from_utf8(bytes).map_err(decode_err)?;
+			let decimal = text.parse().map_err(decode_err)?;
+			Ok(Number::Decimal(decimal).into())
+		}
+		TAG_STRAND => {
+			let bytes = read_bytes(reader)?;
+			Ok(Strand(String::from_utf8(bytes).map_err(decode_err)?).into())
+		}
+		TAG_DURATION => {
+			let mut secs = [0u8; 8];
+			reader.read_exact(&mut secs).map_err(decode_err)?;
+			let mut nanos = [0u8; 4];
+			reader.read_exact(&mut nanos).map_err(decode_err)?;
+			Ok(Duration(std::time::Duration::new(
+				u64::from_be_bytes(secs),
+				u32::from_be_bytes(nanos),
+			))
+			.into())
+		}
+		TAG_DATETIME => {
+			let mut secs = [0u8; 8];
+			reader.read_exact(&mut secs).map_err(decode_err)?;
+			let mut nanos = [0u8; 4];
+			reader.read_exact(&mut nanos).map_err(decode_err)?;
+			let datetime = chrono::Utc
+				.timestamp_opt(i64::from_be_bytes(secs), u32::from_be_bytes(nanos))
+				.single()
+				.ok_or_else(|| decode_err("invalid datetime"))?;
+			Ok(crate::sql::Datetime(datetime).into())
+		}
+		TAG_UUID => {
+			let mut bytes = [0u8; 16];
+			reader.read_exact(&mut bytes).map_err(decode_err)?;
+			Ok(Uuid(uuid::Uuid::from_bytes(bytes)).into())
+		}
+		TAG_ARRAY => {
+			let len = read_varint(reader)?;
+			let mut array = Vec::with_capacity(len as usize);
+			for _ in 0..len {
+				array.push(read_value(reader)?);
+			}
+			Ok(Array(array).into())
+		}
+		TAG_OBJECT => {
+			let len = read_varint(reader)?;
+			let mut object = Object::default();
+			for _ in 0..len {
+				let key = String::from_utf8(read_bytes(reader)?).map_err(decode_err)?;
+				let value = read_value(reader)?;
+				object.insert(key, value);
+			}
+			Ok(object.into())
+		}
+		tag => Err(decode_err(format!("unknown binary wire tag `{tag}`"))),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sql::*;
+
+	fn assert_round_trip<T>(value: T)
+	where
+		T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug,
+	{
+		let bytes = to_vec(&value).unwrap();
+		let decoded: T = from_slice(&bytes).unwrap();
+		assert_eq!(value, decoded);
+	}
+
+	#[test]
+	fn none() {
+		assert_round_trip(None::<u32>);
+	}
+
+	#[test]
+	fn bools() {
+		assert_round_trip(false);
+		assert_round_trip(true);
+	}
+
+	#[test]
+	fn number() {
+		assert_round_trip(Number::Int(-42));
+		assert_round_trip(Number::Float(4.2));
+	}
+
+	#[test]
+	fn strand() {
+		assert_round_trip(Strand("foobar".to_owned()));
+	}
+
+	#[test]
+	fn duration() {
+		assert_round_trip(Duration::default());
+	}
+
+	#[test]
+	fn datetime() {
+		assert_round_trip(Datetime::default());
+	}
+
+	#[test]
+	fn uuid() {
+		assert_round_trip(Uuid::default());
+	}
+
+	#[test]
+	fn array() {
+		assert_round_trip(Array(vec![Value::True, Value::from(1), Value::from("two")]));
+	}
+
+	#[test]
+	fn decimal_is_deterministic_regardless_of_scale() {
+		let a = Number::Decimal("1.5".parse().unwrap());
+		let b = Number::Decimal("1.50".parse().unwrap());
+		assert_eq!(a, b);
+		assert_eq!(to_vec(&a).unwrap(), to_vec(&b).unwrap());
+	}
+
+	#[test]
+	fn object_is_deterministic_regardless_of_insertion_order() {
+		let mut a = Object::default();
+		a.insert("a".to_owned(), Value::from(1));
+		a.insert("b".to_owned(), Value::from(2));
+
+		let mut b = Object::default();
+		b.insert("b".to_owned(), Value::from(2));
+		b.insert("a".to_owned(), Value::from(1));
+
+		assert_eq!(to_vec(&a).unwrap(), to_vec(&b).unwrap());
+	}
+}
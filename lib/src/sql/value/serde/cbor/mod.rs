@@ -0,0 +1,314 @@
+use crate::err::Error;
+use crate::sql::array::Array;
+use crate::sql::object::Object;
+use crate::sql::value::serde::de::from_value;
+use crate::sql::value::serde::ser::value::to_value;
+use crate::sql::Datetime;
+use crate::sql::Duration;
+use crate::sql::Geometry;
+use crate::sql::Id;
+use crate::sql::Number;
+use crate::sql::Strand;
+use crate::sql::Thing;
+use crate::sql::Uuid;
+use crate::sql::Value;
+use chrono::TimeZone;
+use ciborium::value::Value as Cbor;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use storekey::decode::Error as DecodeError;
+use storekey::encode::Error as EncodeError;
+
+// Standard CBOR tags (RFC 8949 / IANA registry).
+const TAG_DATETIME: u64 = 0; // RFC3339 text
+const TAG_UUID: u64 = 37; // binary UUID
+
+// Tags private to this wire format, for the SurrealDB-native types the standard registry has no
+// entry for.
+const TAG_DURATION: u64 = 1_000_000;
+const TAG_DECIMAL: u64 = 1_000_001;
+const TAG_THING: u64 = 1_000_002;
+const TAG_GEOMETRY: u64 = 1_000_003;
+
+fn encode_err(err: impl ToString) -> Error {
+	Error::Encode(EncodeError::Message(err.to_string()))
+}
+
+fn decode_err(err: impl ToString) -> Error {
+	Error::Decode(DecodeError::Message(err.to_string()))
+}
+
+/// Serialize a `T` into CBOR, preserving SurrealDB-native types via semantic tags that plain JSON
+/// would otherwise flatten away.
+pub(crate) fn to_vec<T>(value: &T) -> Result<Vec<u8>, Error>
+where
+	T: Serialize,
+{
+	let cbor = value_to_cbor(&to_value(value)?)?;
+	let mut out = Vec::new();
+	ciborium::into_writer(&cbor, &mut out).map_err(encode_err)?;
+	Ok(out)
+}
+
+/// Read a `T` back from CBOR produced by [`to_vec`] (or from ordinary, untagged CBOR).
+pub(crate) fn from_slice<T>(bytes: &[u8]) -> Result<T, Error>
+where
+	T: DeserializeOwned,
+{
+	let cbor: Cbor = ciborium::from_reader(bytes).map_err(decode_err)?;
+	from_value(cbor_to_value(cbor)?)
+}
+
+fn value_to_cbor(value: &Value) -> Result<Cbor, Error> {
+	Ok(match value {
+		Value::None | Value::Null => Cbor::Null,
+		Value::False => Cbor::Bool(false),
+		Value::True => Cbor::Bool(true),
+		Value::Number(Number::Int(v)) => Cbor::Integer((*v).into()),
+		Value::Number(Number::Float(v)) => Cbor::Float(*v),
+		Value::Number(Number::Decimal(v)) => {
+			Cbor::Tag(TAG_DECIMAL, Box::new(Cbor::Text(v.to_string())))
+		}
+		Value::Strand(Strand(v)) => Cbor::Text(v.clone()),
+		Value::Datetime(Datetime(v)) => {
+			Cbor::Tag(TAG_DATETIME, Box::new(Cbor::Text(v.to_rfc3339())))
+		}
+		Value::Duration(Duration(v)) => Cbor::Tag(
+			TAG_DURATION,
+			Box::new(Cbor::Array(vec![
+				Cbor::Integer(v.as_secs().into()),
+				Cbor::Integer(v.subsec_nanos().into()),
+			])),
+		),
+		Value::Uuid(Uuid(v)) => Cbor::Tag(TAG_UUID, Box::new(Cbor::Bytes(v.as_bytes().to_vec()))),
+		Value::Array(Array(v)) => {
+			Cbor::Array(v.iter().map(value_to_cbor).collect::<Result<_, _>>()?)
+		}
+		Value::Object(Object(v)) => Cbor::Map(
+			v.iter()
+				.map(|(k, v)| Ok((Cbor::Text(k.clone()), value_to_cbor(v)?)))
+				.collect::<Result<_, Error>>()?,
+		),
+		Value::Thing(Thing {
+			tb,
+			id,
+		}) => Cbor::Tag(
+			TAG_THING,
+			Box::new(Cbor::Array(vec![Cbor::Text(tb.clone()), value_to_cbor(&to_value(id)?)?])),
+		),
+		Value::Geometry(geometry) => {
+			// `to_value` on the whole `Geometry` just hands back `Value::Geometry(geometry)`
+			// unchanged (the same passthrough `ser::number::Serializer` uses for `Number`), so we
+			// have to reserialize each variant's inner payload instead, tagging it with the
+			// variant name the way any other externally-tagged enum is represented in this format.
+			let (variant, inner) = match geometry {
+				Geometry::Point(v) => ("Point", to_value(v)?),
+				Geometry::Line(v) => ("Line", to_value(v)?),
+				Geometry::Polygon(v) => ("Polygon", to_value(v)?),
+				Geometry::MultiPoint(v) => ("MultiPoint", to_value(v)?),
+				Geometry::MultiLine(v) => ("MultiLine", to_value(v)?),
+				Geometry::MultiPolygon(v) => ("MultiPolygon", to_value(v)?),
+				Geometry::Collection(v) => ("Collection", to_value(v)?),
+			};
+			Cbor::Tag(
+				TAG_GEOMETRY,
+				Box::new(Cbor::Map(vec![(Cbor::Text(variant.to_owned()), value_to_cbor(&inner)?)])),
+			)
+		}
+		value => return Err(encode_err(format!("`{value}` has no CBOR representation"))),
+	})
+}
+
+fn cbor_to_value(cbor: Cbor) -> Result<Value, Error> {
+	Ok(match cbor {
+		Cbor::Null => Value::Null,
+		Cbor::Bool(false) => Value::False,
+		Cbor::Bool(true) => Value::True,
+		Cbor::Integer(v) => Number::Int(i64::try_from(v).map_err(decode_err)?).into(),
+		Cbor::Float(v) => Number::Float(v).into(),
+		Cbor::Text(v) => Strand(v).into(),
+		Cbor::Bytes(v) => Array(v.into_iter().map(|b| Number::Int(b.into()).into()).collect()).into(),
+		Cbor::Array(v) => {
+			Array(v.into_iter().map(cbor_to_value).collect::<Result<_, _>>()?).into()
+		}
+		Cbor::Map(v) => {
+			let mut object = Object::default();
+			for (key, value) in v {
+				let key = match key {
+					Cbor::Text(key) => key,
+					key => return Err(decode_err(format!("expected a text map key, found `{key:?}`"))),
+				};
+				object.insert(key, cbor_to_value(value)?);
+			}
+			object.into()
+		}
+		Cbor::Tag(TAG_DATETIME, inner) => {
+			let text = match *inner {
+				Cbor::Text(text) => text,
+				inner => return Err(decode_err(format!("expected RFC3339 text, found `{inner:?}`"))),
+			};
+			let datetime = chrono::DateTime::parse_from_rfc3339(&text)
+				.map_err(decode_err)?
+				.with_timezone(&chrono::Utc);
+			Datetime(datetime).into()
+		}
+		Cbor::Tag(1, inner) => {
+			let secs = match *inner {
+				Cbor::Integer(v) => i64::try_from(v).map_err(decode_err)?,
+				Cbor::Float(v) => v as i64,
+				inner => return Err(decode_err(format!("expected an epoch timestamp, found `{inner:?}`"))),
+			};
+			let datetime = chrono::Utc.timestamp_opt(secs, 0).single().ok_or_else(|| {
+				decode_err("invalid epoch timestamp")
+			})?;
+			Datetime(datetime).into()
+		}
+		Cbor::Tag(TAG_UUID, inner) => {
+			let bytes = match *inner {
+				Cbor::Bytes(bytes) => bytes,
+				inner => return Err(decode_err(format!("expected 16 UUID bytes, found `{inner:?}`"))),
+			};
+			let bytes: [u8; 16] =
+				bytes.try_into().map_err(|_| decode_err("expected exactly 16 UUID bytes"))?;
+			Uuid(uuid::Uuid::from_bytes(bytes)).into()
+		}
+		Cbor::Tag(TAG_DURATION, inner) => {
+			let [secs, nanos] = match *inner {
+				Cbor::Array(elements) if elements.len() == 2 => {
+					let mut elements = elements.into_iter();
+					[elements.next().unwrap(), elements.next().unwrap()]
+				}
+				inner => return Err(decode_err(format!("expected `[secs, nanos]`, found `{inner:?}`"))),
+			};
+			let secs = u64::try_from(match secs {
+				Cbor::Integer(v) => v,
+				_ => return Err(decode_err("expected an integer number of seconds")),
+			})
+			.map_err(decode_err)?;
+			let nanos = u32::try_from(match nanos {
+				Cbor::Integer(v) => v,
+				_ => return Err(decode_err("expected an integer number of nanoseconds")),
+			})
+			.map_err(decode_err)?;
+			Duration(std::time::Duration::new(secs, nanos)).into()
+		}
+		Cbor::Tag(TAG_DECIMAL, inner) => {
+			let text = match *inner {
+				Cbor::Text(text) => text,
+				inner => return Err(decode_err(format!("expected a decimal string, found `{inner:?}`"))),
+			};
+			Number::Decimal(text.parse().map_err(decode_err)?).into()
+		}
+		Cbor::Tag(TAG_THING, inner) => {
+			let [tb, id] = match *inner {
+				Cbor::Array(elements) if elements.len() == 2 => {
+					let mut elements = elements.into_iter();
+					[elements.next().unwrap(), elements.next().unwrap()]
+				}
+				inner => return Err(decode_err(format!("expected `[table, id]`, found `{inner:?}`"))),
+			};
+			let tb = match tb {
+				Cbor::Text(tb) => tb,
+				tb => return Err(decode_err(format!("expected a table name, found `{tb:?}`"))),
+			};
+			let id: Id = from_value(cbor_to_value(id)?)?;
+			Value::Thing(Thing {
+				tb,
+				id,
+			})
+		}
+		Cbor::Tag(TAG_GEOMETRY, inner) => {
+			let geometry: Geometry = from_value(cbor_to_value(*inner)?)?;
+			Value::Geometry(geometry)
+		}
+		// An unrecognised tag from ordinary CBOR: keep the payload, drop the tag.
+		Cbor::Tag(_, inner) => cbor_to_value(*inner)?,
+		cbor => return Err(decode_err(format!("unsupported CBOR value `{cbor:?}`"))),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sql;
+	use crate::sql::*;
+
+	fn assert_round_trip<T>(value: T)
+	where
+		T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug,
+	{
+		let bytes = to_vec(&value).unwrap();
+		let decoded: T = from_slice(&bytes).unwrap();
+		assert_eq!(value, decoded);
+	}
+
+	#[test]
+	fn none() {
+		assert_round_trip(None::<u32>);
+	}
+
+	#[test]
+	fn bools() {
+		assert_round_trip(false);
+		assert_round_trip(true);
+	}
+
+	#[test]
+	fn number() {
+		assert_round_trip(Number::Int(-42));
+		assert_round_trip(Number::Float(4.2));
+	}
+
+	#[test]
+	fn strand() {
+		assert_round_trip(Strand("foobar".to_owned()));
+	}
+
+	#[test]
+	fn duration() {
+		assert_round_trip(Duration::default());
+	}
+
+	#[test]
+	fn datetime() {
+		assert_round_trip(Datetime::default());
+	}
+
+	#[test]
+	fn uuid() {
+		assert_round_trip(Uuid::default());
+	}
+
+	#[test]
+	fn array() {
+		assert_round_trip(Array(vec![Value::True, Value::from(1), Value::from("two")]));
+	}
+
+	#[test]
+	fn object() {
+		let mut object = Object::default();
+		object.insert("a".to_owned(), Value::from(1));
+		assert_round_trip(object);
+	}
+
+	#[test]
+	fn thing() {
+		assert_round_trip(sql::thing("foo:bar").unwrap());
+	}
+
+	#[test]
+	fn geometry() {
+		assert_round_trip(Geometry::Collection(Vec::new()));
+	}
+
+	#[test]
+	fn ordinary_cbor_still_decodes() {
+		let mut out = Vec::new();
+		ciborium::into_writer(&Cbor::Map(vec![(Cbor::Text("a".to_owned()), Cbor::Integer(1.into()))]), &mut out)
+			.unwrap();
+		let value: Value = from_slice(&out).unwrap();
+		let mut expected = Object::default();
+		expected.insert("a".to_owned(), Value::from(1));
+		assert_eq!(value, Value::Object(expected));
+	}
+}
@@ -0,0 +1,233 @@
+use crate::err::Error;
+use crate::sql::array::Array;
+use crate::sql::object::Object;
+use crate::sql::Number;
+use crate::sql::Strand;
+use crate::sql::Value;
+use bigdecimal::BigDecimal;
+use std::collections::HashSet;
+use std::str::FromStr;
+use storekey::decode::Error as DecodeError;
+use storekey::encode::Error as EncodeError;
+
+fn encode_err(err: impl ToString) -> Error {
+	Error::Encode(EncodeError::Message(err.to_string()))
+}
+
+fn decode_err(err: impl ToString) -> Error {
+	Error::Decode(DecodeError::Message(err.to_string()))
+}
+
+/// Export a `Value::Array` of `Value::Object`s as CSV, for data export and bulk load.
+///
+/// The header is the union of every row's keys, in the order each key was first seen (rows in
+/// order, each row's keys sorted), so rows with different shapes still line up under one table.
+pub(crate) fn to_csv(value: &Value) -> Result<String, Error> {
+	let rows = match value {
+		Value::Array(Array(rows)) => rows,
+		value => return Err(encode_err(format!("expected an array of objects, found `{value}`"))),
+	};
+
+	let mut header = Vec::new();
+	let mut seen = HashSet::new();
+	for row in rows {
+		let mut keys: Vec<&String> = row_fields(row)?.keys().collect();
+		keys.sort();
+		for key in keys {
+			if seen.insert(key.clone()) {
+				header.push(key.clone());
+			}
+		}
+	}
+
+	let mut writer = csv::WriterBuilder::new().from_writer(Vec::new());
+	writer.write_record(&header).map_err(encode_err)?;
+	for row in rows {
+		let map = row_fields(row)?;
+		let record: Vec<String> = header
+			.iter()
+			.map(|key| match map.get(key) {
+				Some(value) => field_to_csv(value),
+				None => Ok(String::new()),
+			})
+			.collect::<Result<_, Error>>()?;
+		writer.write_record(&record).map_err(encode_err)?;
+	}
+
+	let bytes = writer.into_inner().map_err(encode_err)?;
+	String::from_utf8(bytes).map_err(encode_err)
+}
+
+/// Parse CSV produced by [`to_csv`] (or any CSV with a header row) back into a `Value::Array` of
+/// `Value::Object`s.
+pub(crate) fn from_csv(input: &str) -> Result<Value, Error> {
+	let mut reader = csv::ReaderBuilder::new().from_reader(input.as_bytes());
+	let headers = reader.headers().map_err(decode_err)?.clone();
+
+	let mut rows = Vec::new();
+	for record in reader.records() {
+		let record = record.map_err(decode_err)?;
+		let mut object = Object::default();
+		for (key, cell) in headers.iter().zip(record.iter()) {
+			object.insert(key.to_owned(), field_from_csv(cell)?);
+		}
+		rows.push(Value::Object(object));
+	}
+	Ok(Value::Array(Array(rows)))
+}
+
+fn row_fields(row: &Value) -> Result<&std::collections::HashMap<String, Value>, Error> {
+	match row {
+		Value::Object(Object(map)) => Ok(map),
+		row => Err(encode_err(format!("expected an object row, found `{row}`"))),
+	}
+}
+
+fn field_to_csv(value: &Value) -> Result<String, Error> {
+	Ok(match value {
+		Value::None | Value::Null => String::new(),
+		Value::False => "false".to_owned(),
+		Value::True => "true".to_owned(),
+		Value::Number(n) => n.to_string(),
+		Value::Strand(Strand(v)) => v.clone(),
+		Value::Datetime(v) => v.0.to_rfc3339(),
+		Value::Uuid(v) => v.0.to_string(),
+		Value::Array(_) | Value::Object(_) => {
+			serde_json::to_string(value).map_err(encode_err)?
+		}
+		value => return Err(encode_err(format!("`{value}` has no CSV cell representation"))),
+	})
+}
+
+fn field_from_csv(cell: &str) -> Result<Value, Error> {
+	if cell.is_empty() {
+		return Ok(Value::None);
+	}
+	match cell {
+		"true" => return Ok(Value::True),
+		"false" => return Ok(Value::False),
+		_ => {}
+	}
+	if let Ok(v) = cell.parse::<i64>() {
+		return Ok(Number::Int(v).into());
+	}
+	if let Ok(v) = BigDecimal::from_str(cell) {
+		return Ok(Number::Decimal(v).into());
+	}
+	if let Ok(v) = cell.parse::<f64>() {
+		return Ok(Number::Float(v).into());
+	}
+	if (cell.starts_with('{') && cell.ends_with('}'))
+		|| (cell.starts_with('[') && cell.ends_with(']'))
+	{
+		if let Ok(value) = serde_json::from_str::<Value>(cell) {
+			return Ok(value);
+		}
+	}
+	Ok(Strand(cell.to_owned()).into())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sql::*;
+
+	fn array(rows: Vec<Object>) -> Value {
+		Value::Array(Array(rows.into_iter().map(Value::Object).collect()))
+	}
+
+	#[test]
+	fn round_trip() {
+		let mut alice = Object::default();
+		alice.insert("name".to_owned(), Value::from("Alice"));
+		alice.insert("age".to_owned(), Value::from(30));
+		alice.insert("active".to_owned(), Value::True);
+
+		let mut bob = Object::default();
+		bob.insert("name".to_owned(), Value::from("Bob"));
+		bob.insert("age".to_owned(), Value::from(25));
+		bob.insert("active".to_owned(), Value::False);
+
+		let expected = array(vec![alice, bob]);
+		let csv = to_csv(&expected).unwrap();
+		let decoded = from_csv(&csv).unwrap();
+		assert_eq!(expected, decoded);
+	}
+
+	#[test]
+	fn missing_keys_become_empty_fields() {
+		let mut a = Object::default();
+		a.insert("a".to_owned(), Value::from(1));
+		a.insert("b".to_owned(), Value::from(2));
+
+		let mut b = Object::default();
+		b.insert("a".to_owned(), Value::from(3));
+
+		let csv = to_csv(&array(vec![a, b])).unwrap();
+		assert!(csv.contains("a,b"));
+
+		let decoded = from_csv(&csv).unwrap();
+		let Value::Array(Array(rows)) = decoded else {
+			panic!("expected an array");
+		};
+		let Value::Object(Object(second)) = &rows[1] else {
+			panic!("expected an object");
+		};
+		assert_eq!(second.get("b"), Some(&Value::None));
+	}
+
+	#[test]
+	fn decimal_round_trips_as_decimal() {
+		let mut row = Object::default();
+		row.insert("price".to_owned(), Value::from(Number::Decimal("3.14".parse().unwrap())));
+
+		let expected = array(vec![row]);
+		let csv = to_csv(&expected).unwrap();
+		let decoded = from_csv(&csv).unwrap();
+		assert_eq!(expected, decoded);
+
+		let Value::Array(Array(rows)) = decoded else {
+			panic!("expected an array");
+		};
+		let Value::Object(Object(row)) = &rows[0] else {
+			panic!("expected an object");
+		};
+		assert_eq!(row.get("price"), Some(&Value::Number(Number::Decimal("3.14".parse().unwrap()))));
+	}
+
+	#[test]
+	fn float_comes_back_as_decimal() {
+		// CSV cells carry no type tag, so a fractional `Number::Float` is indistinguishable from
+		// a `Number::Decimal` once written out as text - it comes back as `Decimal`, not `Float`.
+		// This is a known, accepted lossy conversion: documented here so a future change to
+		// `field_from_csv`'s parsing order doesn't silently flip it back without notice.
+		let mut row = Object::default();
+		row.insert("price".to_owned(), Value::from(Number::Float(3.14)));
+
+		let csv = to_csv(&array(vec![row])).unwrap();
+		let decoded = from_csv(&csv).unwrap();
+
+		let Value::Array(Array(rows)) = decoded else {
+			panic!("expected an array");
+		};
+		let Value::Object(Object(row)) = &rows[0] else {
+			panic!("expected an object");
+		};
+		assert_eq!(row.get("price"), Some(&Value::Number(Number::Decimal("3.14".parse().unwrap()))));
+	}
+
+	#[test]
+	fn nested_values_round_trip_as_embedded_json() {
+		let mut row = Object::default();
+		row.insert("tags".to_owned(), Value::Array(Array(vec![Value::from("a"), Value::from("b")])));
+
+		let mut nested = Object::default();
+		nested.insert("city".to_owned(), Value::from("Paris"));
+		row.insert("address".to_owned(), Value::Object(nested));
+
+		let expected = array(vec![row]);
+		let csv = to_csv(&expected).unwrap();
+		let decoded = from_csv(&csv).unwrap();
+		assert_eq!(expected, decoded);
+	}
+}
@@ -0,0 +1,738 @@
+use crate::err::Error;
+use crate::sql;
+use crate::sql::array::Array;
+use crate::sql::object::Object;
+use crate::sql::value::serde::ser::value::to_value;
+use crate::sql::value::Value;
+use crate::sql::Block;
+use crate::sql::Datetime;
+use crate::sql::Duration;
+use crate::sql::Edges;
+use crate::sql::Expression;
+use crate::sql::Future;
+use crate::sql::Geometry;
+use crate::sql::Ident;
+use crate::sql::Idiom;
+use crate::sql::Number;
+use crate::sql::Param;
+use crate::sql::Range;
+use crate::sql::Regex;
+use crate::sql::Strand;
+use crate::sql::Table;
+use crate::sql::Thing;
+use crate::sql::Uuid;
+use serde::de;
+use serde::de::DeserializeOwned;
+use serde::de::DeserializeSeed;
+use serde::de::EnumAccess;
+use serde::de::Error as _;
+use serde::de::IntoDeserializer;
+use serde::de::MapAccess;
+use serde::de::SeqAccess;
+use serde::de::VariantAccess;
+use serde::de::Visitor;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+use storekey::decode::Error as DecodeError;
+
+/// Convert a `surrealdb::sql::Value` into a `T`, the inverse of [`super::ser::value::to_value`].
+pub(crate) fn from_value<T>(value: Value) -> Result<T, Error>
+where
+	T: DeserializeOwned,
+{
+	T::deserialize(Deserializer(value))
+}
+
+impl de::Error for Error {
+	fn custom<T>(msg: T) -> Self
+	where
+		T: Display,
+	{
+		Self::Decode(DecodeError::Message(msg.to_string()))
+	}
+}
+
+fn number_to_i64(number: &Number) -> Result<i64, Error> {
+	match number {
+		Number::Int(v) => Ok(*v),
+		Number::Float(v) => Ok(*v as i64),
+		Number::Decimal(v) => {
+			v.to_string().parse().map_err(|_| Error::TryFromError(v.to_string(), "i64"))
+		}
+	}
+}
+
+fn number_to_u64(number: &Number) -> Result<u64, Error> {
+	match number {
+		Number::Int(v) => u64::try_from(*v).map_err(|_| Error::TryFromError(v.to_string(), "u64")),
+		Number::Float(v) => Ok(*v as u64),
+		Number::Decimal(v) => {
+			v.to_string().parse().map_err(|_| Error::TryFromError(v.to_string(), "u64"))
+		}
+	}
+}
+
+fn number_to_f64(number: &Number) -> Result<f64, Error> {
+	match number {
+		Number::Int(v) => Ok(*v as f64),
+		Number::Float(v) => Ok(*v),
+		Number::Decimal(v) => {
+			v.to_string().parse().map_err(|_| Error::TryFromError(v.to_string(), "f64"))
+		}
+	}
+}
+
+/// Bridges a native Rust value (e.g. the `chrono`/`uuid`/`std::time` type wrapped by one of our
+/// `sql` newtypes) back through [`to_value`] and this same [`Deserializer`], so we never have to
+/// special-case the wire shape those foreign `Serialize` impls happen to produce.
+fn reserialize(value: &impl Serialize) -> Result<Deserializer, Error> {
+	Ok(Deserializer(to_value(value)?))
+}
+
+pub(super) struct Deserializer(Value);
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+	type Error = Error;
+
+	fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		match self.0 {
+			Value::None | Value::Null => visitor.visit_unit(),
+			Value::False => visitor.visit_bool(false),
+			Value::True => visitor.visit_bool(true),
+			Value::Number(Number::Int(v)) => visitor.visit_i64(v),
+			Value::Number(Number::Float(v)) => visitor.visit_f64(v),
+			Value::Number(Number::Decimal(v)) => visitor.visit_string(v.to_string()),
+			Value::Strand(Strand(v)) => visitor.visit_string(v),
+			Value::Array(Array(v)) => visitor.visit_seq(ValueSeqAccess(v.into_iter())),
+			Value::Object(Object(v)) => visitor.visit_map(ValueMapAccess::new(v)),
+			value => Err(Error::custom(format!("cannot deserialize `{value}` without a concrete target type"))),
+		}
+	}
+
+	fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		match self.0 {
+			Value::False => visitor.visit_bool(false),
+			Value::True => visitor.visit_bool(true),
+			value => Err(Error::custom(format!("expected a bool, found `{value}`"))),
+		}
+	}
+
+	fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		visitor.visit_i8(number_to_i64(&self.number()?)? as i8)
+	}
+
+	fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		visitor.visit_i16(number_to_i64(&self.number()?)? as i16)
+	}
+
+	fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		visitor.visit_i32(number_to_i64(&self.number()?)? as i32)
+	}
+
+	fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		visitor.visit_i64(number_to_i64(&self.number()?)?)
+	}
+
+	fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		visitor.visit_u8(number_to_u64(&self.number()?)? as u8)
+	}
+
+	fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		visitor.visit_u16(number_to_u64(&self.number()?)? as u16)
+	}
+
+	fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		visitor.visit_u32(number_to_u64(&self.number()?)? as u32)
+	}
+
+	fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		visitor.visit_u64(number_to_u64(&self.number()?)?)
+	}
+
+	fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		visitor.visit_f32(number_to_f64(&self.number()?)? as f32)
+	}
+
+	fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		visitor.visit_f64(number_to_f64(&self.number()?)?)
+	}
+
+	fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		match self.0 {
+			Value::Strand(Strand(v)) if v.chars().count() == 1 => {
+				visitor.visit_char(v.chars().next().expect("checked len above"))
+			}
+			value => Err(Error::custom(format!("expected a single character, found `{value}`"))),
+		}
+	}
+
+	fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		self.deserialize_string(visitor)
+	}
+
+	fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		match self.0 {
+			Value::Strand(Strand(v)) => visitor.visit_string(v),
+			Value::Number(Number::Decimal(v)) => visitor.visit_string(v.to_string()),
+			value => Err(Error::custom(format!("expected a string, found `{value}`"))),
+		}
+	}
+
+	fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		match self.0 {
+			Value::Array(Array(v)) => {
+				let mut bytes = Vec::with_capacity(v.len());
+				for value in v {
+					match value {
+						Value::Number(n) => bytes.push(number_to_u64(&n)? as u8),
+						value => {
+							return Err(Error::custom(format!("expected a byte, found `{value}`")))
+						}
+					}
+				}
+				visitor.visit_byte_buf(bytes)
+			}
+			value => Err(Error::custom(format!("expected bytes, found `{value}`"))),
+		}
+	}
+
+	fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		self.deserialize_bytes(visitor)
+	}
+
+	fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		match self.0 {
+			Value::None | Value::Null => visitor.visit_none(),
+			value => visitor.visit_some(Deserializer(value)),
+		}
+	}
+
+	fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		match self.0 {
+			Value::None | Value::Null => visitor.visit_unit(),
+			value => Err(Error::custom(format!("expected unit, found `{value}`"))),
+		}
+	}
+
+	fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		self.deserialize_unit(visitor)
+	}
+
+	fn deserialize_newtype_struct<V>(
+		self,
+		name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		match (name, self.0) {
+			(sql::duration::TOKEN, Value::Duration(Duration(inner))) => {
+				visitor.visit_newtype_struct(reserialize(&inner)?)
+			}
+			(sql::uuid::TOKEN, Value::Uuid(Uuid(inner))) => {
+				visitor.visit_newtype_struct(reserialize(&inner)?)
+			}
+			(sql::datetime::TOKEN, Value::Datetime(Datetime(inner))) => {
+				visitor.visit_newtype_struct(reserialize(&inner)?)
+			}
+			(sql::table::TOKEN, Value::Table(Table(inner))) => {
+				visitor.visit_newtype_struct(reserialize(&inner)?)
+			}
+			(sql::param::TOKEN, Value::Param(Param(Ident(inner)))) => {
+				visitor.visit_newtype_struct(reserialize(&inner)?)
+			}
+			(sql::idiom::TOKEN, Value::Idiom(Idiom(inner))) => {
+				visitor.visit_newtype_struct(reserialize(&inner)?)
+			}
+			(sql::regex::TOKEN, Value::Regex(Regex(inner))) => {
+				visitor.visit_newtype_struct(reserialize(&inner)?)
+			}
+			(sql::block::TOKEN, Value::Block(block)) => {
+				let Block(inner) = *block;
+				visitor.visit_newtype_struct(reserialize(&inner)?)
+			}
+			(sql::future::TOKEN, Value::Future(future)) => {
+				let Future(Block(inner)) = *future;
+				visitor.visit_newtype_struct(reserialize(&inner)?)
+			}
+			(_, value) => visitor.visit_newtype_struct(Deserializer(value)),
+		}
+	}
+
+	fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		match self.0 {
+			Value::Array(Array(v)) => visitor.visit_seq(ValueSeqAccess(v.into_iter())),
+			value => Err(Error::custom(format!("expected an array, found `{value}`"))),
+		}
+	}
+
+	fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_tuple_struct<V>(
+		self,
+		_name: &'static str,
+		_len: usize,
+		visitor: V,
+	) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		match self.0 {
+			Value::Object(Object(v)) => visitor.visit_map(ValueMapAccess::new(v)),
+			value => Err(Error::custom(format!("expected an object, found `{value}`"))),
+		}
+	}
+
+	fn deserialize_struct<V>(
+		self,
+		name: &'static str,
+		_fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		match (name, self.0) {
+			(sql::thing::TOKEN, Value::Thing(Thing { tb, id })) => {
+				let mut map = HashMap::with_capacity(2);
+				map.insert("tb".to_owned(), Value::Strand(Strand(tb)));
+				map.insert("id".to_owned(), id.into());
+				visitor.visit_map(ValueMapAccess::new(map))
+			}
+			(sql::expression::TOKEN, Value::Expression(expr)) => {
+				let Expression {
+					l,
+					o,
+					r,
+				} = *expr;
+				let mut map = HashMap::with_capacity(3);
+				map.insert("l".to_owned(), l);
+				map.insert("o".to_owned(), reserialize(&o)?.0);
+				map.insert("r".to_owned(), r);
+				visitor.visit_map(ValueMapAccess::new(map))
+			}
+			(sql::edges::TOKEN, Value::Edges(edges)) => {
+				let Edges {
+					dir,
+					from,
+					what,
+				} = *edges;
+				let mut map = HashMap::with_capacity(3);
+				map.insert("dir".to_owned(), reserialize(&dir)?.0);
+				map.insert("from".to_owned(), Value::Thing(from));
+				map.insert("what".to_owned(), reserialize(&what)?.0);
+				visitor.visit_map(ValueMapAccess::new(map))
+			}
+			(sql::range::TOKEN, Value::Range(range)) => {
+				let Range {
+					tb,
+					beg,
+					end,
+				} = *range;
+				let mut map = HashMap::with_capacity(3);
+				map.insert("tb".to_owned(), Value::Strand(Strand(tb)));
+				map.insert("beg".to_owned(), reserialize(&beg)?.0);
+				map.insert("end".to_owned(), reserialize(&end)?.0);
+				visitor.visit_map(ValueMapAccess::new(map))
+			}
+			(_, Value::Object(Object(v))) => visitor.visit_map(ValueMapAccess::new(v)),
+			(_, value) => Err(Error::custom(format!("expected `{name}`, found `{value}`"))),
+		}
+	}
+
+	fn deserialize_enum<V>(
+		self,
+		name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		match (name, self.0) {
+			(sql::number::TOKEN, Value::Number(number)) => {
+				let (variant, inner) = match number {
+					Number::Int(v) => ("Int", Value::Number(Number::Int(v))),
+					Number::Float(v) => ("Float", Value::Number(Number::Float(v))),
+					Number::Decimal(v) => ("Decimal", Value::Number(Number::Decimal(v))),
+				};
+				visitor.visit_enum(ValueEnumAccess(variant.to_owned(), inner))
+			}
+			(sql::geometry::TOKEN, Value::Geometry(geometry)) => {
+				let (variant, inner) = match geometry {
+					Geometry::Point(v) => ("Point", reserialize(&v)?),
+					Geometry::Line(v) => ("Line", reserialize(&v)?),
+					Geometry::Polygon(v) => ("Polygon", reserialize(&v)?),
+					Geometry::MultiPoint(v) => ("MultiPoint", reserialize(&v)?),
+					Geometry::MultiLine(v) => ("MultiLine", reserialize(&v)?),
+					Geometry::MultiPolygon(v) => ("MultiPolygon", reserialize(&v)?),
+					Geometry::Collection(v) => ("Collection", reserialize(&v)?),
+				};
+				visitor.visit_enum(ValueEnumAccess(variant.to_owned(), inner.0))
+			}
+			(_, Value::Strand(Strand(variant))) => {
+				visitor.visit_enum(ValueEnumAccess(variant, Value::None))
+			}
+			(_, Value::Object(Object(mut map))) if map.len() == 1 => {
+				let variant = map.keys().next().expect("checked len above").clone();
+				let value = map.remove(&variant).expect("just read this key");
+				visitor.visit_enum(ValueEnumAccess(variant, value))
+			}
+			(_, value) => Err(Error::custom(format!("expected an enum, found `{value}`"))),
+		}
+	}
+
+	fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		self.deserialize_string(visitor)
+	}
+
+	fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		self.deserialize_any(visitor)
+	}
+
+	serde::forward_to_deserialize_any! {
+		i128 u128
+	}
+}
+
+impl Deserializer {
+	fn number(&self) -> Result<Number, Error> {
+		match &self.0 {
+			Value::Number(v) => Ok(v.clone()),
+			value => Err(Error::custom(format!("expected a number, found `{value}`"))),
+		}
+	}
+}
+
+struct ValueSeqAccess(std::vec::IntoIter<Value>);
+
+impl<'de> SeqAccess<'de> for ValueSeqAccess {
+	type Error = Error;
+
+	fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+	where
+		T: DeserializeSeed<'de>,
+	{
+		match self.0.next() {
+			Some(value) => seed.deserialize(Deserializer(value)).map(Some),
+			None => Ok(None),
+		}
+	}
+}
+
+struct ValueMapAccess {
+	iter: std::collections::hash_map::IntoIter<String, Value>,
+	value: Option<Value>,
+}
+
+impl ValueMapAccess {
+	fn new(map: HashMap<String, Value>) -> Self {
+		Self {
+			iter: map.into_iter(),
+			value: None,
+		}
+	}
+}
+
+impl<'de> MapAccess<'de> for ValueMapAccess {
+	type Error = Error;
+
+	fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+	where
+		K: DeserializeSeed<'de>,
+	{
+		match self.iter.next() {
+			Some((key, value)) => {
+				self.value = Some(value);
+				seed.deserialize(key.into_deserializer()).map(Some)
+			}
+			None => Ok(None),
+		}
+	}
+
+	fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+	where
+		V: DeserializeSeed<'de>,
+	{
+		let value = self.value.take().expect("next_value_seed called before next_key_seed");
+		seed.deserialize(Deserializer(value))
+	}
+}
+
+struct ValueEnumAccess(String, Value);
+
+impl<'de> EnumAccess<'de> for ValueEnumAccess {
+	type Error = Error;
+	type Variant = ValueVariantAccess;
+
+	fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Error>
+	where
+		V: DeserializeSeed<'de>,
+	{
+		let variant = seed.deserialize(self.0.into_deserializer())?;
+		Ok((variant, ValueVariantAccess(self.1)))
+	}
+}
+
+struct ValueVariantAccess(Value);
+
+impl<'de> VariantAccess<'de> for ValueVariantAccess {
+	type Error = Error;
+
+	fn unit_variant(self) -> Result<(), Error> {
+		Ok(())
+	}
+
+	fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+	where
+		T: DeserializeSeed<'de>,
+	{
+		seed.deserialize(Deserializer(self.0))
+	}
+
+	fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		de::Deserializer::deserialize_seq(Deserializer(self.0), visitor)
+	}
+
+	fn struct_variant<V>(
+		self,
+		_fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error>
+	where
+		V: Visitor<'de>,
+	{
+		de::Deserializer::deserialize_map(Deserializer(self.0), visitor)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sql::*;
+	use std::ops::Bound;
+
+	fn assert_round_trip<T>(value: T)
+	where
+		T: serde::Serialize + DeserializeOwned + PartialEq + std::fmt::Debug,
+	{
+		let encoded = to_value(&value).unwrap();
+		let decoded: T = from_value(encoded).unwrap();
+		assert_eq!(value, decoded);
+	}
+
+	#[test]
+	fn none() {
+		assert_round_trip(None::<u32>);
+	}
+
+	#[test]
+	fn r#false() {
+		assert_round_trip(false);
+	}
+
+	#[test]
+	fn r#true() {
+		assert_round_trip(true);
+	}
+
+	#[test]
+	fn number() {
+		assert_round_trip(Number::Int(Default::default()));
+		assert_round_trip(Number::Float(Default::default()));
+	}
+
+	#[test]
+	fn strand() {
+		assert_round_trip(Strand("foobar".to_owned()));
+	}
+
+	#[test]
+	fn duration() {
+		assert_round_trip(Duration::default());
+	}
+
+	#[test]
+	fn datetime() {
+		assert_round_trip(Datetime::default());
+	}
+
+	#[test]
+	fn uuid() {
+		assert_round_trip(Uuid::default());
+	}
+
+	#[test]
+	fn array() {
+		assert_round_trip(Array::default());
+	}
+
+	#[test]
+	fn object() {
+		assert_round_trip(Object::default());
+	}
+
+	#[test]
+	fn geometry() {
+		assert_round_trip(Geometry::Collection(Vec::new()));
+	}
+
+	#[test]
+	fn table() {
+		assert_round_trip(Table("foo".to_owned()));
+	}
+
+	#[test]
+	fn param() {
+		assert_round_trip(Param::default());
+	}
+
+	#[test]
+	fn idiom() {
+		assert_round_trip(Idiom::default());
+	}
+
+	#[test]
+	fn regex() {
+		assert_round_trip(Regex::default());
+	}
+
+	#[test]
+	fn block() {
+		assert_round_trip(Box::new(Block::default()));
+	}
+
+	#[test]
+	fn future() {
+		assert_round_trip(Box::new(Future(Block::default())));
+	}
+
+	#[test]
+	fn expression() {
+		assert_round_trip(Box::new(Expression {
+			l: "foo".into(),
+			o: Operator::Equal,
+			r: "Bar".into(),
+		}));
+	}
+
+	#[test]
+	fn edges() {
+		assert_round_trip(Box::new(Edges {
+			dir: Dir::In,
+			from: sql::thing("foo:bar").unwrap(),
+			what: Tables(vec!["foo".into()]),
+		}));
+	}
+
+	#[test]
+	fn range() {
+		assert_round_trip(Box::new(Range {
+			tb: "foo".to_owned(),
+			beg: Bound::Included("foo".into()),
+			end: Bound::Unbounded,
+		}));
+	}
+
+	#[test]
+	fn nested_struct() {
+		#[derive(Debug, PartialEq, Serialize, serde::Deserialize)]
+		struct FooBar {
+			foo: String,
+			bar: i32,
+			baz: Option<u32>,
+		}
+
+		assert_round_trip(FooBar {
+			foo: "Foo".to_owned(),
+			bar: -1,
+			baz: None,
+		});
+	}
+}
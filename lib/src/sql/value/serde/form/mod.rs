@@ -0,0 +1,147 @@
+use crate::err::Error;
+use crate::sql::array::Array;
+use crate::sql::object::Object;
+use crate::sql::value::serde::de::from_value;
+use crate::sql::Number;
+use crate::sql::Strand;
+use crate::sql::Value;
+use serde::de::DeserializeOwned;
+
+/// Parse an `application/x-www-form-urlencoded` query or body directly into a `Value::Object`.
+///
+/// Repeated keys accumulate into a `Value::Array`, and bracketed keys such as `filter[status]`
+/// nest into sub-objects, mirroring how most web frameworks expand form fields.
+pub(crate) fn from_form(input: &str) -> Result<Value, Error> {
+	let mut root = Object::default();
+	for (key, value) in form_urlencoded::parse(input.as_bytes()) {
+		let path = parse_key(&key);
+		insert(&mut root, &path, coerce(&value));
+	}
+	Ok(Value::Object(root))
+}
+
+/// Parse a form body straight into a `T`, pairing [`from_form`] with [`super::de::from_value`].
+pub(crate) fn from_form_as<T>(input: &str) -> Result<T, Error>
+where
+	T: DeserializeOwned,
+{
+	from_value(from_form(input)?)
+}
+
+fn parse_key(key: &str) -> Vec<String> {
+	let Some(bracket) = key.find('[') else {
+		return vec![key.to_owned()];
+	};
+	let mut parts = vec![key[..bracket].to_owned()];
+	let mut rest = &key[bracket..];
+	while let Some(stripped) = rest.strip_prefix('[') {
+		let Some(end) = stripped.find(']') else {
+			break;
+		};
+		parts.push(stripped[..end].to_owned());
+		rest = &stripped[end + 1..];
+	}
+	parts
+}
+
+fn coerce(raw: &str) -> Value {
+	match raw {
+		"" => return Value::None,
+		"true" => return Value::True,
+		"false" => return Value::False,
+		_ => {}
+	}
+	if let Ok(v) = raw.parse::<i64>() {
+		return Number::Int(v).into();
+	}
+	if let Ok(v) = raw.parse::<f64>() {
+		return Number::Float(v).into();
+	}
+	Strand(raw.to_owned()).into()
+}
+
+fn insert(object: &mut Object, path: &[String], value: Value) {
+	let [key, rest @ ..] = path else {
+		return;
+	};
+	if rest.is_empty() {
+		accumulate(object, key, value);
+		return;
+	}
+	let entry = object.0.entry(key.clone()).or_insert_with(|| Value::Object(Object::default()));
+	let Value::Object(inner) = entry else {
+		let mut inner = Object::default();
+		insert(&mut inner, rest, value);
+		*entry = Value::Object(inner);
+		return;
+	};
+	insert(inner, rest, value);
+}
+
+fn accumulate(object: &mut Object, key: &str, value: Value) {
+	match object.0.remove(key) {
+		None => {
+			object.insert(key.to_owned(), value);
+		}
+		Some(Value::Array(Array(mut existing))) => {
+			existing.push(value);
+			object.insert(key.to_owned(), Value::Array(Array(existing)));
+		}
+		Some(existing) => {
+			object.insert(key.to_owned(), Value::Array(Array(vec![existing, value])));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sql::*;
+
+	fn object(value: Value) -> Object {
+		let Value::Object(object) = value else {
+			panic!("expected an object");
+		};
+		object
+	}
+
+	#[test]
+	fn simple_pairs() {
+		let decoded = object(from_form("name=Ada&age=36").unwrap());
+		assert_eq!(decoded.get("name"), Some(&Value::from("Ada")));
+		assert_eq!(decoded.get("age"), Some(&Value::from(36)));
+	}
+
+	#[test]
+	fn percent_decoding_and_plus_as_space() {
+		let decoded = object(from_form("q=hello%20world+again").unwrap());
+		assert_eq!(decoded.get("q"), Some(&Value::from("hello world again")));
+	}
+
+	#[test]
+	fn repeated_keys_accumulate_into_an_array() {
+		let decoded = object(from_form("tag=a&tag=b&tag=c").unwrap());
+		assert_eq!(
+			decoded.get("tag"),
+			Some(&Value::Array(Array(vec![Value::from("a"), Value::from("b"), Value::from("c")])))
+		);
+	}
+
+	#[test]
+	fn bracketed_keys_nest_into_sub_objects() {
+		let decoded = object(from_form("filter[status]=active&filter[limit]=10").unwrap());
+		let Some(Value::Object(filter)) = decoded.get("filter") else {
+			panic!("expected a nested object");
+		};
+		assert_eq!(filter.get("status"), Some(&Value::from("active")));
+		assert_eq!(filter.get("limit"), Some(&Value::from(10)));
+	}
+
+	#[test]
+	fn booleans_numbers_and_empty_values() {
+		let decoded = object(from_form("active=true&count=3.5&missing=").unwrap());
+		assert_eq!(decoded.get("active"), Some(&Value::True));
+		assert_eq!(decoded.get("count"), Some(&Value::from(3.5)));
+		assert_eq!(decoded.get("missing"), Some(&Value::None));
+	}
+}